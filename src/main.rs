@@ -7,20 +7,20 @@ use std::path::PathBuf;
 use std::process;
 
 use pbr;
+use sha2::Digest;
 use structopt::{clap::AppSettings, StructOpt};
 
 /// Command line options.
 #[derive(Debug, StructOpt)]
 #[structopt(raw(global_settings = "&[AppSettings::DeriveDisplayOrder]"))]
 struct Opt {
-    /// The source file to copy from.
-    #[structopt(name = "SRC", parse(from_os_str))]
-    src: PathBuf,
-
-    /// The destination file to copy to.  Will create the file
-    /// if it does not exist.
-    #[structopt(name = "DST", parse(from_os_str))]
-    dst: PathBuf,
+    /// The files to operate on.  In the normal and `--bundle` modes the
+    /// final path is the destination (`DST`, created if it does not exist)
+    /// and every path before it is a source (`SRC`) to copy from;
+    /// `--unbundle` takes the bundle file followed by the directory to
+    /// extract into.
+    #[structopt(name = "PATH", parse(from_os_str), required = true, min_values = 2)]
+    paths: Vec<PathBuf>,
 
     /// The byte offset in the source file to start reading from.
     /// Must not be larger than the file in question.
@@ -43,11 +43,127 @@ struct Opt {
     #[structopt(short = "c", long = "count")]
     count: Option<u64>,
 
+    /// Concatenate every `SRC` into `DST`, prefixing a self-describing
+    /// header so the originals can later be recovered with `--unbundle`.
+    #[structopt(long = "bundle", conflicts_with = "unbundle")]
+    bundle: bool,
+
+    /// Extract a bundle created with `--bundle`: the first path is the
+    /// bundle file and the second is the directory to recreate its files
+    /// under.
+    #[structopt(long = "unbundle")]
+    unbundle: bool,
+
+    /// Compute a digest of the bytes as they stream through the copy,
+    /// printing it to stderr on completion.  One of `crc32`, `sha256`, or
+    /// `blake3`.
+    #[structopt(long = "hash")]
+    hash: Option<HashKind>,
+
+    /// After copying, re-read the written destination region and confirm
+    /// its digest matches the source's.  Requires `--hash`.
+    #[structopt(long = "verify")]
+    verify: bool,
+
+    /// Preserve holes instead of materializing zero blocks: all-zero runs
+    /// in the stream are turned into holes in the destination rather than
+    /// written out.  Cannot be used when `--dst-offset` lands inside an
+    /// existing region of the destination file.
+    #[structopt(long = "sparse")]
+    sparse: bool,
+
+    /// Number of worker threads to split the copy across.  Each worker
+    /// copies a contiguous chunk of the range using positional I/O, so the
+    /// threads never contend on a shared file cursor.  A big win off fast
+    /// SSDs/NVMe where a single thread can't saturate the device.
+    #[structopt(short = "j", long = "jobs", default_value = "1")]
+    jobs: usize,
+
     /// Verbose output, with progress bar.
     #[structopt(short = "v", long = "verbose")]
     verbose: bool,
 }
 
+impl Opt {
+    /// The source paths: every path except the final destination.
+    fn srcs(&self) -> &[PathBuf] {
+        &self.paths[..self.paths.len() - 1]
+    }
+
+    /// The sole source path, for the single-file copy modes.
+    fn src(&self) -> &PathBuf {
+        &self.paths[0]
+    }
+
+    /// The destination path: always the final positional argument.
+    fn dst(&self) -> &PathBuf {
+        self.paths.last().expect("clap guarantees >= 2 paths")
+    }
+}
+
+/// The magic number stamped at the start of every bundle.
+const BUNDLE_MAGIC: &[u8; 4] = b"BCPB";
+/// The bundle format version this build reads and writes.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Which digest algorithm `--hash` should use.
+#[derive(Debug, Clone, Copy)]
+enum HashKind {
+    Crc32,
+    Sha256,
+    Blake3,
+}
+
+impl std::str::FromStr for HashKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "crc32" => Ok(HashKind::Crc32),
+            "sha256" => Ok(HashKind::Sha256),
+            "blake3" => Ok(HashKind::Blake3),
+            other => Err(format!("unknown hash {:?} (want crc32, sha256, or blake3)", other)),
+        }
+    }
+}
+
+/// A streaming digest over the copied bytes.  Wraps whichever algorithm
+/// `--hash` selected behind a common `update`/`finalize` interface.
+enum Hasher {
+    Crc32(crc32fast::Hasher),
+    Sha256(sha2::Sha256),
+    // Boxed because `blake3::Hasher` is far larger than the other variants.
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(kind: HashKind) -> Hasher {
+        match kind {
+            HashKind::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            HashKind::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashKind::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Crc32(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+        }
+    }
+
+    /// Consume the hasher and return the digest as a lowercase hex string.
+    fn finalize(self) -> String {
+        match self {
+            Hasher::Crc32(h) => format!("{:08x}", h.finalize()),
+            Hasher::Sha256(h) => format!("{:x}", h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+        }
+    }
+}
+
 /// Print an error message and quit.
 fn error(msg: &str) -> ! {
     eprintln!("ERROR: {}", msg);
@@ -59,7 +175,7 @@ fn error(msg: &str) -> ! {
 /// there's no point in asking for it twice.
 fn sanity_check(opt: &Opt) -> u64 {
     // Check src file length.
-    let src_metadata = opt.src.metadata().unwrap_or_else(|e| {
+    let src_metadata = opt.src().metadata().unwrap_or_else(|e| {
         let errmsg = format!("Could not get metadata for source file: {:?}", e);
         error(&errmsg)
     });
@@ -78,11 +194,11 @@ fn sanity_check(opt: &Opt) -> u64 {
     }
 
     // Check dest file length and properties.
-    if opt.dst.exists() {
-        if opt.dst.is_dir() {
+    if opt.dst().exists() {
+        if opt.dst().is_dir() {
             error("Destination must be a file.");
         }
-        let dst_metadata = opt.dst.metadata().unwrap_or_else(|e| {
+        let dst_metadata = opt.dst().metadata().unwrap_or_else(|e| {
             let errmsg = format!("Could not get metadata for destination file: {:?}", e);
             error(&errmsg)
         });
@@ -91,6 +207,12 @@ fn sanity_check(opt: &Opt) -> u64 {
         if dst_metadata.len() < opt.dst_offset {
             error("destination offset > destination file size");
         }
+        // Sparse copying grows the file by seeking past its end; if the
+        // offset sits inside existing data, seeking over zero runs would
+        // leave the old bytes in place instead of punching holes.
+        if opt.sparse && opt.dst_offset < dst_metadata.len() {
+            error("--sparse requires the destination offset to be at or past the end of the file.");
+        }
     } else if opt.dst_offset > 0 {
         error("destination file cannot have an offset if the file does not exist; the results of trying to seek past the end of a file are system-defined and thus probably not what you want.")
     }
@@ -104,11 +226,11 @@ fn sanity_check(opt: &Opt) -> u64 {
 
 /// Actually do the copy.
 fn copy_stuff(opt: &Opt, src_len: u64) {
-    let mut src = fs::File::open(&opt.src).expect("Should never happen?");
+    let mut src = fs::File::open(opt.src()).expect("Should never happen?");
     let mut dst = fs::OpenOptions::new()
         .write(true)
         .create(true)
-        .open(&opt.dst)
+        .open(opt.dst())
         .unwrap_or_else(|e| {
             let errmsg = format!("Could not open destination file for writing: {:?}", e);
             error(&errmsg)
@@ -119,12 +241,190 @@ fn copy_stuff(opt: &Opt, src_len: u64) {
     dst.seek(io::SeekFrom::Start(opt.dst_offset))
         .expect("Should never happen?");
 
-    let copy_len = opt.count.unwrap_or(src_len);
-    let mut src = src.take(copy_len);
+    // Without an explicit count we copy from the source offset to EOF, so
+    // the length is the bytes *remaining* after the offset, not the whole
+    // file.  The positional and sparse paths treat this as an absolute
+    // count and would otherwise read past EOF or pad a spurious trailing
+    // hole.
+    let copy_len = opt.count.unwrap_or(src_len - opt.src_offset);
+
+    if opt.jobs > 1 {
+        parallel_copy(opt, copy_len);
+        return;
+    }
+
+    let mut pb = if opt.verbose {
+        let mut progress = pbr::ProgressBar::new(copy_len);
+        progress.set_units(pbr::Units::Bytes);
+        Some(progress)
+    } else {
+        None
+    };
+
+    // A digest can only be computed over bytes that actually pass through
+    // userspace, so `--hash` forces the generic loop and skips the
+    // zero-copy kernel fast path.
+    let mut hasher = opt.hash.map(Hasher::new);
+
+    // Sparse copying has to inspect every byte to spot zero runs, so it
+    // gets its own loop and never uses the zero-copy kernel fast path.
+    if opt.sparse {
+        sparse_copy(&mut src, &mut dst, opt, copy_len, &mut pb, &mut hasher);
+        report_hash(opt, copy_len, hasher);
+        return;
+    }
+
+    // On Linux we can usually hand the whole copy to the kernel and never
+    // touch the bytes in userspace at all.  If that path bows out partway
+    // through (old kernel, cross-device copy, ...) it tells us how many
+    // bytes it managed so the generic loop below can finish the rest.
+    let copied;
+    #[cfg(target_os = "linux")]
+    {
+        copied = if hasher.is_none() {
+            accelerated_copy(&src, &dst, opt, copy_len, &mut pb)
+        } else {
+            0
+        };
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        copied = 0;
+    }
+
+    if copied < copy_len {
+        // Pick up wherever the fast path stopped (its `sendfile` fallback
+        // moves the destination cursor, so seek both files to the current
+        // logical offsets).
+        src.seek(io::SeekFrom::Start(opt.src_offset + copied))
+            .expect("Should never happen?");
+        dst.seek(io::SeekFrom::Start(opt.dst_offset + copied))
+            .expect("Should never happen?");
+        generic_copy(&mut src, &mut dst, opt, copy_len - copied, &mut pb, &mut hasher);
+    }
+
+    report_hash(opt, copy_len, hasher);
+}
+
+/// Print the source digest (if `--hash` was given) and, when `--verify` is
+/// set, re-hash the written destination region and confirm it matches.
+fn report_hash(opt: &Opt, copy_len: u64, hasher: Option<Hasher>) {
+    if let (Some(hasher), Some(kind)) = (hasher, opt.hash) {
+        let digest = hasher.finalize();
+        eprintln!("{:?}: {}", kind, digest);
+        if opt.verify {
+            let actual = digest_region(opt.dst(), opt.dst_offset, copy_len, opt.buffer_size, kind);
+            if actual == digest {
+                eprintln!("verify: OK");
+            } else {
+                error(&format!("verify: MISMATCH (destination digest {})", actual));
+            }
+        }
+    }
+}
+
+/// Compute the digest of `len` bytes of `path` starting at `offset`, using
+/// a second positional read pass.  Used by `--verify` to re-hash the
+/// just-written destination region without disturbing any file cursor.
+fn digest_region(
+    path: &std::path::Path,
+    offset: u64,
+    len: u64,
+    buffer_size: usize,
+    kind: HashKind,
+) -> String {
+    #[cfg(unix)]
+    use std::os::unix::fs::FileExt;
+    #[cfg(windows)]
+    use std::os::windows::fs::FileExt;
 
-    // Basically stolen from io::copy().
-    // We want a little more control over what's happening
-    // than that gives us.
+    let file = fs::File::open(path)
+        .unwrap_or_else(|e| error(&format!("Could not reopen file to verify: {:?}", e)));
+    let mut hasher = Hasher::new(kind);
+    let mut buf = vec![0; buffer_size];
+    let mut read = 0u64;
+    while read < len {
+        let want = std::cmp::min(buffer_size as u64, len - read) as usize;
+        #[cfg(unix)]
+        let n = file
+            .read_at(&mut buf[..want], offset + read)
+            .unwrap_or_else(|e| error(&format!("Error reading file: {:?}", e)));
+        #[cfg(windows)]
+        let n = file
+            .seek_read(&mut buf[..want], offset + read)
+            .unwrap_or_else(|e| error(&format!("Error reading file: {:?}", e)));
+        if n == 0 {
+            error("Destination shorter than expected during verification.");
+        }
+        hasher.update(&buf[..n]);
+        read += n as u64;
+    }
+    hasher.finalize()
+}
+
+/// Copy `copy_len` bytes using `opt.jobs` worker threads.  The byte range
+/// is split into that many contiguous chunks, each handed to a worker that
+/// uses positional I/O (`read_at`/`write_at`, or `seek_read`/`seek_write`
+/// on Windows) so no thread mutates a shared file cursor.  The destination
+/// is pre-sized so every worker writes into an already-allocated region.
+fn parallel_copy(opt: &Opt, copy_len: u64) {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    // Pre-allocate the whole destination region up front.  Open without
+    // truncating so an existing tail past the copied range is preserved,
+    // matching the single-threaded path.
+    let dst = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(opt.dst())
+        .unwrap_or_else(|e| {
+            let errmsg = format!("Could not open destination file for writing: {:?}", e);
+            error(&errmsg)
+        });
+    // Grow the file if it's too short, but never shrink it: `set_len` would
+    // otherwise truncate data living past the copied region.
+    let needed = opt.dst_offset + copy_len;
+    let current = dst
+        .metadata()
+        .map(|m| m.len())
+        .unwrap_or_else(|e| error(&format!("Could not stat destination file: {:?}", e)));
+    if current < needed {
+        dst.set_len(needed).unwrap_or_else(|e| {
+            let errmsg = format!("Could not pre-size destination file: {:?}", e);
+            error(&errmsg)
+        });
+    }
+    drop(dst);
+
+    let progress = Arc::new(AtomicU64::new(0));
+    let jobs = opt.jobs as u64;
+    let chunk = copy_len / jobs;
+
+    let handles: Vec<_> = (0..jobs)
+        .map(|i| {
+            // The last worker mops up any remainder from the division.
+            let this_len = if i == jobs - 1 {
+                copy_len - chunk * i
+            } else {
+                chunk
+            };
+            let src_off = opt.src_offset + chunk * i;
+            let dst_off = opt.dst_offset + chunk * i;
+            let src_path = opt.src().clone();
+            let dst_path = opt.dst().clone();
+            let buffer_size = opt.buffer_size;
+            let progress = Arc::clone(&progress);
+            std::thread::spawn(move || {
+                copy_chunk(
+                    &src_path, src_off, &dst_path, dst_off, this_len, buffer_size, &progress,
+                )
+            })
+        })
+        .collect();
+
+    // Drive the progress bar from the shared counter while the workers run.
     let mut pb = if opt.verbose {
         let mut progress = pbr::ProgressBar::new(copy_len);
         progress.set_units(pbr::Units::Bytes);
@@ -132,6 +432,102 @@ fn copy_stuff(opt: &Opt, src_len: u64) {
     } else {
         None
     };
+    if let Some(ref mut p) = pb {
+        let mut last = 0;
+        loop {
+            let done = progress.load(Ordering::Relaxed);
+            p.add(done - last);
+            last = done;
+            if done >= copy_len {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+    }
+
+    for h in handles {
+        h.join().expect("A copy worker thread panicked");
+    }
+}
+
+/// A single parallel worker: copy `len` bytes from `src_path` at
+/// `src_off` to `dst_path` at `dst_off` using positional reads and writes,
+/// looping over short reads/writes and reporting progress into `progress`.
+fn copy_chunk(
+    src_path: &std::path::Path,
+    src_off: u64,
+    dst_path: &std::path::Path,
+    dst_off: u64,
+    len: u64,
+    buffer_size: usize,
+    progress: &std::sync::atomic::AtomicU64,
+) {
+    use std::sync::atomic::Ordering;
+    #[cfg(unix)]
+    use std::os::unix::fs::FileExt;
+    #[cfg(windows)]
+    use std::os::windows::fs::FileExt;
+
+    let src = fs::File::open(src_path).expect("Should never happen?");
+    let dst = fs::OpenOptions::new()
+        .write(true)
+        .open(dst_path)
+        .expect("Should never happen?");
+
+    let mut buf = vec![0; buffer_size];
+    let mut copied = 0u64;
+    while copied < len {
+        let want = std::cmp::min(buffer_size as u64, len - copied) as usize;
+        // Read one buffer's worth, tolerating short positional reads.
+        let mut got = 0;
+        while got < want {
+            #[cfg(unix)]
+            let n = src
+                .read_at(&mut buf[got..want], src_off + copied + got as u64)
+                .unwrap_or_else(|e| error(&format!("Error reading file: {:?}", e)));
+            #[cfg(windows)]
+            let n = src
+                .seek_read(&mut buf[got..want], src_off + copied + got as u64)
+                .unwrap_or_else(|e| error(&format!("Error reading file: {:?}", e)));
+            // A positional read of an in-bounds range should never come up
+            // empty; if it does the source shrank underneath us, and looping
+            // on a zero-length read would spin forever.
+            if n == 0 {
+                error("Source shrank during copy (unexpected end of source file).");
+            }
+            got += n;
+        }
+        // Write it back at the matching destination offset.
+        let mut put = 0;
+        while put < got {
+            #[cfg(unix)]
+            let n = dst
+                .write_at(&buf[put..got], dst_off + copied + put as u64)
+                .unwrap_or_else(|e| error(&format!("Error writing file: {:?}", e)));
+            #[cfg(windows)]
+            let n = dst
+                .seek_write(&buf[put..got], dst_off + copied + put as u64)
+                .unwrap_or_else(|e| error(&format!("Error writing file: {:?}", e)));
+            put += n;
+        }
+        copied += got as u64;
+        progress.fetch_add(got as u64, Ordering::Relaxed);
+    }
+}
+
+/// The portable buffered read/write loop.  Copies `remaining` bytes from
+/// `src` to `dst` forcing every byte through userspace, updating the
+/// progress bar as it goes.  Basically stolen from `io::copy()`; we want a
+/// little more control over what's happening than that gives us.
+fn generic_copy(
+    src: &mut fs::File,
+    dst: &mut fs::File,
+    opt: &Opt,
+    remaining: u64,
+    pb: &mut Option<pbr::ProgressBar<io::Stdout>>,
+    hasher: &mut Option<Hasher>,
+) {
+    let mut src = src.take(remaining);
     let mut buf = vec![0; opt.buffer_size];
     loop {
         let len = match src.read(&mut buf) {
@@ -143,6 +539,11 @@ fn copy_stuff(opt: &Opt, src_len: u64) {
                 error(&errmsg)
             }
         };
+        // The bytes are already here in `buf`, so folding them into the
+        // digest costs one call and no extra I/O.
+        if let Some(ref mut h) = hasher {
+            h.update(&buf[..len]);
+        }
         dst.write_all(&buf[..len]).unwrap_or_else(|e| {
             let errmsg = format!("Error reading file: {:?}", e);
             error(&errmsg)
@@ -153,11 +554,588 @@ fn copy_stuff(opt: &Opt, src_len: u64) {
     }
 }
 
+/// The sparse copy loop: copy `copy_len` logical bytes from `src` to
+/// `dst` (both already seeked to their offsets), turning all-zero runs
+/// into holes instead of writing them out.  On Linux it also uses
+/// `SEEK_DATA` on the source to skip directly over existing holes without
+/// ever reading them.  The destination's logical length is fixed up with
+/// `set_len` at the end so a trailing hole still counts toward the size.
+fn sparse_copy(
+    src: &mut fs::File,
+    dst: &mut fs::File,
+    opt: &Opt,
+    copy_len: u64,
+    pb: &mut Option<pbr::ProgressBar<io::Stdout>>,
+    hasher: &mut Option<Hasher>,
+) {
+    let mut remaining = copy_len;
+    let mut buf = vec![0; opt.buffer_size];
+    while remaining > 0 {
+        // On Linux, jump over any hole at the current source position
+        // rather than reading a buffer full of zeros.
+        #[cfg(target_os = "linux")]
+        {
+            let skipped = skip_source_hole(src, dst, remaining);
+            if skipped > 0 {
+                // The skipped span reads back as zeros from the
+                // destination, so fold those zeros into the digest to stay
+                // consistent with the read path (and with `--verify`).
+                if let Some(ref mut h) = hasher {
+                    let mut left = skipped;
+                    while left > 0 {
+                        let n = std::cmp::min(left, buf.len() as u64) as usize;
+                        for b in &mut buf[..n] {
+                            *b = 0;
+                        }
+                        h.update(&buf[..n]);
+                        left -= n as u64;
+                    }
+                }
+                if let Some(ref mut p) = pb {
+                    p.add(skipped);
+                }
+                remaining -= skipped;
+                continue;
+            }
+        }
+
+        let want = std::cmp::min(buf.len() as u64, remaining) as usize;
+        let len = match src.read(&mut buf[..want]) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => error(&format!("Error reading file: {:?}", e)),
+        };
+        if let Some(ref mut h) = hasher {
+            h.update(&buf[..len]);
+        }
+
+        // Walk the buffer, punching zero runs as holes and writing the
+        // non-zero spans verbatim.
+        let mut i = 0;
+        while i < len {
+            if buf[i] == 0 {
+                let start = i;
+                while i < len && buf[i] == 0 {
+                    i += 1;
+                }
+                dst.seek(io::SeekFrom::Current((i - start) as i64))
+                    .unwrap_or_else(|e| error(&format!("Error seeking destination: {:?}", e)));
+            } else {
+                let start = i;
+                while i < len && buf[i] != 0 {
+                    i += 1;
+                }
+                dst.write_all(&buf[start..i])
+                    .unwrap_or_else(|e| error(&format!("Error writing file: {:?}", e)));
+            }
+        }
+        if let Some(ref mut p) = pb {
+            p.add(len as u64);
+        }
+        remaining -= len as u64;
+    }
+
+    // A copy that ends in a hole leaves the destination short; grow it to
+    // the intended logical length so trailing holes are recorded.
+    dst.set_len(opt.dst_offset + copy_len)
+        .unwrap_or_else(|e| error(&format!("Could not finalize destination size: {:?}", e)));
+}
+
+/// Linux-only optimization for [`sparse_copy`]: if the source's current
+/// position sits inside a hole, advance both the source and destination
+/// past it (bounded by `remaining`) without reading, and return the number
+/// of bytes skipped.  Returns 0 when the current position holds data.
+#[cfg(target_os = "linux")]
+fn skip_source_hole(src: &mut fs::File, dst: &mut fs::File, remaining: u64) -> u64 {
+    let cur = src
+        .stream_position()
+        .unwrap_or_else(|e| error(&format!("Error seeking source: {:?}", e)));
+    // Where does the next data region start at or after `cur`?  `None`
+    // means the rest of the file is one big hole.
+    let next_data = seek_data(src, cur).unwrap_or(cur + remaining);
+    let hole = next_data.saturating_sub(cur).min(remaining);
+    // `lseek(SEEK_DATA)` moved the underlying file offset; pin it back to
+    // exactly where the next read should begin.
+    src.seek(io::SeekFrom::Start(cur + hole))
+        .unwrap_or_else(|e| error(&format!("Error seeking source: {:?}", e)));
+    if hole > 0 {
+        dst.seek(io::SeekFrom::Current(hole as i64))
+            .unwrap_or_else(|e| error(&format!("Error seeking destination: {:?}", e)));
+    }
+    hole
+}
+
+/// Return the offset of the next data region at or after `from` using
+/// `lseek(SEEK_DATA)`, or `None` if the rest of the file is a hole.
+#[cfg(target_os = "linux")]
+fn seek_data(src: &fs::File, from: u64) -> Option<u64> {
+    use std::os::unix::io::AsRawFd;
+    let ret = unsafe { libc::lseek(src.as_raw_fd(), from as libc::off_t, libc::SEEK_DATA) };
+    if ret < 0 {
+        // ENXIO => no data between `from` and EOF; any other error just
+        // disables the optimization and falls back to reading.
+        if io::Error::last_os_error().raw_os_error() == Some(libc::ENXIO) {
+            None
+        } else {
+            Some(from)
+        }
+    } else {
+        Some(ret as u64)
+    }
+}
+
+/// Linux-only fast path that lets the kernel move the data directly,
+/// without a userspace round trip.  Tries `copy_file_range` first (which
+/// can do reflink/in-kernel same-filesystem copies), then `sendfile`, and
+/// returns the number of bytes it successfully copied.  A return value
+/// smaller than `copy_len` means it hit an unsupported/cross-device case
+/// and the caller should finish the remainder with [`generic_copy`].
+#[cfg(target_os = "linux")]
+fn accelerated_copy(
+    src: &fs::File,
+    dst: &fs::File,
+    opt: &Opt,
+    copy_len: u64,
+    pb: &mut Option<pbr::ProgressBar<io::Stdout>>,
+) -> u64 {
+    use std::os::unix::io::AsRawFd;
+
+    let src_fd = src.as_raw_fd();
+    let dst_fd = dst.as_raw_fd();
+    let mut src_off = opt.src_offset as libc::loff_t;
+    let mut dst_off = opt.dst_offset as libc::loff_t;
+    let mut copied: u64 = 0;
+
+    while copied < copy_len {
+        let remaining = (copy_len - copied) as usize;
+        let ret = unsafe {
+            libc::copy_file_range(src_fd, &mut src_off, dst_fd, &mut dst_off, remaining, 0)
+        };
+        if ret == 0 {
+            // EOF: nothing more the kernel will give us.
+            break;
+        } else if ret > 0 {
+            copied += ret as u64;
+            if let Some(ref mut p) = pb {
+                p.add(ret as u64);
+            }
+        } else {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EINTR) => continue,
+                // Unsupported kernel or a cross-device copy: fall back to
+                // `sendfile` once; whatever it cannot copy is left to the
+                // generic loop in the caller.
+                Some(libc::ENOSYS) | Some(libc::EINVAL) | Some(libc::EXDEV) => {
+                    // `sendfile` has no output-offset argument and writes at
+                    // the dst fd's cursor, so position it where
+                    // `copy_file_range` left off before handing over.
+                    unsafe { libc::lseek(dst_fd, dst_off, libc::SEEK_SET) };
+                    return copied
+                        + sendfile_copy(src_fd, &mut src_off, dst_fd, copy_len - copied, pb)
+                }
+                _ => {
+                    let errmsg = format!("Error copying file: {:?}", err);
+                    error(&errmsg)
+                }
+            }
+        }
+    }
+    copied
+}
+
+/// Second-tier Linux fast path using `sendfile`, tried when
+/// `copy_file_range` is unavailable.  Returns the number of bytes copied;
+/// a short return falls through to the generic loop in the caller.
+#[cfg(target_os = "linux")]
+fn sendfile_copy(
+    src_fd: libc::c_int,
+    src_off: &mut libc::loff_t,
+    dst_fd: libc::c_int,
+    copy_len: u64,
+    pb: &mut Option<pbr::ProgressBar<io::Stdout>>,
+) -> u64 {
+    let mut copied: u64 = 0;
+    while copied < copy_len {
+        let remaining = (copy_len - copied) as usize;
+        // Pass an explicit source offset so `sendfile` picks up exactly
+        // where `copy_file_range` left off and updates it for us, rather
+        // than relying on (and clobbering) the shared file cursor.
+        let ret = unsafe { libc::sendfile(dst_fd, src_fd, src_off, remaining) };
+        if ret == 0 {
+            break;
+        } else if ret > 0 {
+            copied += ret as u64;
+            if let Some(ref mut p) = pb {
+                p.add(ret as u64);
+            }
+        } else {
+            let err = io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::EINTR) => continue,
+                Some(libc::ENOSYS) | Some(libc::EINVAL) | Some(libc::EXDEV) => break,
+                _ => {
+                    let errmsg = format!("Error copying file: {:?}", err);
+                    error(&errmsg)
+                }
+            }
+        }
+    }
+    copied
+}
+
+/// Exits if the options for a `--bundle` run don't make sense: every
+/// source must exist and must not be a directory.
+fn sanity_check_bundle(opt: &Opt) {
+    if opt.buffer_size == 0 {
+        error("buffer size = 0.  Finishing your copy would take a long, long time.");
+    }
+    for src in opt.srcs() {
+        let meta = src.metadata().unwrap_or_else(|e| {
+            let errmsg = format!("Could not get metadata for source file {:?}: {:?}", src, e);
+            error(&errmsg)
+        });
+        if meta.is_dir() {
+            let errmsg = format!("Source {:?} is a directory; only files can be bundled.", src);
+            error(&errmsg)
+        }
+    }
+}
+
+/// Encode a path as raw OS bytes so non-UTF-8 names survive a bundle
+/// round-trip losslessly.
+#[cfg(unix)]
+fn path_to_bytes(path: &std::path::Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+#[cfg(not(unix))]
+fn path_to_bytes(path: &std::path::Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+/// Inverse of [`path_to_bytes`].
+#[cfg(unix)]
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+#[cfg(not(unix))]
+fn bytes_to_path(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Reduce a source path to a safe, normalized *relative* path for storage:
+/// drop any root/prefix and `.` components and `..` entirely, so a bundle
+/// can never be crafted (or accidentally made) to write outside the
+/// extraction directory.
+fn sanitize_relative(path: &std::path::Path) -> PathBuf {
+    use std::path::Component;
+    let mut out = PathBuf::new();
+    for comp in path.components() {
+        if let Component::Normal(c) = comp {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Concatenate every source into the destination behind a self-describing
+/// header, reusing the byte-accurate copy core for the payload.  The
+/// on-disk layout is:
+///
+/// ```text
+/// magic: [u8; 4]            "BCPB"
+/// version: u32 (LE)
+/// count: u32 (LE)           number of entries
+/// for each entry:
+///     path_length: u32 (LE)
+///     path_bytes: [u8; path_length]
+///     file_size: u64 (LE)
+/// payload: raw file contents, back to back, in entry order
+/// ```
+fn bundle(opt: &Opt) {
+    sanity_check_bundle(opt);
+
+    let mut dst = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(opt.dst())
+        .unwrap_or_else(|e| {
+            let errmsg = format!("Could not open destination file for writing: {:?}", e);
+            error(&errmsg)
+        });
+
+    let srcs = opt.srcs();
+    let mut sizes = Vec::with_capacity(srcs.len());
+    let mut header = Vec::new();
+    header.extend_from_slice(BUNDLE_MAGIC);
+    header.extend_from_slice(&BUNDLE_VERSION.to_le_bytes());
+    header.extend_from_slice(&(srcs.len() as u32).to_le_bytes());
+    for src in srcs {
+        let size = src.metadata().expect("checked in sanity_check_bundle").len();
+        // Store a normalized relative path as raw OS bytes: relative so
+        // `--unbundle` stays inside its output directory, raw bytes so
+        // non-UTF-8 names round-trip.
+        let path_bytes = path_to_bytes(&sanitize_relative(src));
+        header.extend_from_slice(&(path_bytes.len() as u32).to_le_bytes());
+        header.extend_from_slice(&path_bytes);
+        header.extend_from_slice(&size.to_le_bytes());
+        sizes.push(size);
+    }
+    dst.write_all(&header).unwrap_or_else(|e| {
+        let errmsg = format!("Error writing bundle header: {:?}", e);
+        error(&errmsg)
+    });
+
+    // Stream each source's recorded number of bytes into the destination.
+    for (src, &size) in srcs.iter().zip(&sizes) {
+        let mut input = fs::File::open(src).unwrap_or_else(|e| {
+            let errmsg = format!("Could not open source file {:?}: {:?}", src, e);
+            error(&errmsg)
+        });
+        generic_copy(&mut input, &mut dst, opt, size, &mut None, &mut None);
+    }
+}
+
+/// Read a bundle's header and recreate each recorded file under the output
+/// directory, copying exactly `file_size` bytes per entry out of the
+/// bundle using the same offset/count copy machinery.  Validates that
+/// the magic and version match and that every payload offset stays within
+/// the bundle.
+fn unbundle(opt: &Opt) {
+    if opt.paths.len() != 2 {
+        error("--unbundle takes exactly a bundle file and an output directory.");
+    }
+    if opt.buffer_size == 0 {
+        error("buffer size = 0.  Finishing your copy would take a long, long time.");
+    }
+    let bundle_path = &opt.paths[0];
+    let out_dir = &opt.paths[1];
+
+    let mut input = fs::File::open(bundle_path).unwrap_or_else(|e| {
+        let errmsg = format!("Could not open bundle file: {:?}", e);
+        error(&errmsg)
+    });
+    let total = input
+        .metadata()
+        .map(|m| m.len())
+        .unwrap_or_else(|e| error(&format!("Could not stat bundle: {:?}", e)));
+
+    let read_u32 = |input: &mut fs::File| -> u32 {
+        let mut b = [0u8; 4];
+        input
+            .read_exact(&mut b)
+            .unwrap_or_else(|e| error(&format!("Truncated bundle header: {:?}", e)));
+        u32::from_le_bytes(b)
+    };
+    let read_u64 = |input: &mut fs::File| -> u64 {
+        let mut b = [0u8; 8];
+        input
+            .read_exact(&mut b)
+            .unwrap_or_else(|e| error(&format!("Truncated bundle header: {:?}", e)));
+        u64::from_le_bytes(b)
+    };
+
+    let mut magic = [0u8; 4];
+    input
+        .read_exact(&mut magic)
+        .unwrap_or_else(|e| error(&format!("Truncated bundle header: {:?}", e)));
+    if &magic != BUNDLE_MAGIC {
+        error("Not a bcp bundle (bad magic number).");
+    }
+    let version = read_u32(&mut input);
+    if version != BUNDLE_VERSION {
+        error(&format!("Unsupported bundle version {}.", version));
+    }
+    let count = read_u32(&mut input);
+
+    // Collect the header entries first, then stream payloads.
+    let mut entries = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let path_len = read_u32(&mut input);
+        let mut path_bytes = vec![0u8; path_len as usize];
+        input
+            .read_exact(&mut path_bytes)
+            .unwrap_or_else(|e| error(&format!("Truncated bundle header: {:?}", e)));
+        let path = bytes_to_path(&path_bytes);
+        // Defense in depth: reject anything that could escape `out_dir`,
+        // even from a corrupt or hand-crafted bundle.
+        use std::path::Component;
+        let escapes = path.components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+        if escapes {
+            error(&format!(
+                "Bundle entry {:?} would extract outside the output directory.",
+                path
+            ));
+        }
+        let size = read_u64(&mut input);
+        entries.push((path, size));
+    }
+
+    // The payload starts at the current position; verify every entry's
+    // byte range stays within the bundle before touching the filesystem.
+    let mut offset = input
+        .stream_position()
+        .unwrap_or_else(|e| error(&format!("Could not locate bundle payload: {:?}", e)));
+    for (path, size) in &entries {
+        // Checked arithmetic so a crafted `size` can't wrap past the bound.
+        let end = offset.checked_add(*size).unwrap_or_else(|| {
+            error(&format!("Bundle entry {:?} has an invalid size.", path))
+        });
+        if end > total {
+            error(&format!(
+                "Bundle entry {:?} extends past the end of the bundle.",
+                path
+            ));
+        }
+        offset = end;
+    }
+
+    for (path, size) in &entries {
+        let dest = out_dir.join(path);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .unwrap_or_else(|e| error(&format!("Could not create directory: {:?}", e)));
+        }
+        let mut out = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&dest)
+            .unwrap_or_else(|e| error(&format!("Could not create {:?}: {:?}", dest, e)));
+        generic_copy(&mut input, &mut out, opt, *size, &mut None, &mut None);
+    }
+}
+
 fn main() {
     let opt = Opt::from_args();
-    let src_len = sanity_check(&opt);
-    copy_stuff(&opt, src_len);
+    if opt.bundle {
+        bundle(&opt);
+    } else if opt.unbundle {
+        unbundle(&opt);
+    } else {
+        if opt.paths.len() != 2 {
+            error("Copying takes exactly one source and one destination; use --bundle for multiple sources.");
+        }
+        if opt.verify && opt.hash.is_none() {
+            error("--verify requires --hash to know which digest to compare.");
+        }
+        if opt.hash.is_some() && opt.jobs > 1 {
+            error("--hash cannot be combined with --jobs > 1.");
+        }
+        if opt.sparse && opt.jobs > 1 {
+            error("--sparse cannot be combined with --jobs > 1.");
+        }
+        let src_len = sanity_check(&opt);
+        copy_stuff(&opt, src_len);
+    }
 }
 
 #[cfg(test)]
-mod test {}
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// A process-unique scratch directory, created fresh for each test.
+    fn scratch() -> PathBuf {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("bcp-test-{}-{}", process::id(), n));
+        fs::create_dir_all(&dir).expect("could not create scratch dir");
+        dir
+    }
+
+    /// An `Opt` with everything at its default but the given paths.
+    fn opt_with(paths: Vec<PathBuf>) -> Opt {
+        Opt {
+            paths,
+            src_offset: 0,
+            dst_offset: 0,
+            buffer_size: 4096,
+            count: None,
+            bundle: false,
+            unbundle: false,
+            hash: None,
+            verify: false,
+            sparse: false,
+            jobs: 1,
+            verbose: false,
+        }
+    }
+
+    #[test]
+    fn bundle_unbundle_roundtrip() {
+        let dir = scratch();
+        let a = dir.join("a.bin");
+        let b = dir.join("nested/b.bin");
+        fs::create_dir_all(b.parent().unwrap()).unwrap();
+        let a_data: Vec<u8> = (0..5000u32).map(|i| (i % 256) as u8).collect();
+        let b_data: Vec<u8> = (0..1234u32).map(|i| (i * 7 % 256) as u8).collect();
+        fs::write(&a, &a_data).unwrap();
+        fs::write(&b, &b_data).unwrap();
+
+        let archive = dir.join("out.bcpb");
+        let mut bundle_opt = opt_with(vec![a.clone(), b.clone(), archive.clone()]);
+        bundle_opt.bundle = true;
+        bundle(&bundle_opt);
+
+        let out = dir.join("extracted");
+        let unbundle_opt = opt_with(vec![archive, out.clone()]);
+        unbundle(&unbundle_opt);
+
+        // Paths are stored relative and normalized: the root is dropped and
+        // every remaining component, directories included, is recreated
+        // under the output directory.
+        assert_eq!(fs::read(out.join(sanitize_relative(&a))).unwrap(), a_data);
+        assert_eq!(fs::read(out.join(sanitize_relative(&b))).unwrap(), b_data);
+    }
+
+    #[test]
+    fn jobs_copy_matches_single_thread() {
+        let dir = scratch();
+        let src = dir.join("src.bin");
+        let data: Vec<u8> = (0..100_003u32).map(|i| (i * 31 % 256) as u8).collect();
+        fs::write(&src, &data).unwrap();
+
+        let dst = dir.join("dst.bin");
+        let mut opt = opt_with(vec![src, dst.clone()]);
+        opt.jobs = 4;
+        let src_len = sanity_check(&opt);
+        copy_stuff(&opt, src_len);
+
+        assert_eq!(fs::read(&dst).unwrap(), data);
+    }
+
+    #[test]
+    fn jobs_copy_preserves_destination_tail() {
+        let dir = scratch();
+        let src = dir.join("src.bin");
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 256) as u8).collect();
+        fs::write(&src, &data).unwrap();
+
+        // Pre-existing destination larger than the copied region: the tail
+        // past `count` must survive the parallel copy.
+        let dst = dir.join("dst.bin");
+        let existing = vec![0xabu8; 4096];
+        fs::write(&dst, &existing).unwrap();
+
+        let mut opt = opt_with(vec![src, dst.clone()]);
+        opt.jobs = 2;
+        opt.count = Some(1000);
+        let src_len = sanity_check(&opt);
+        copy_stuff(&opt, src_len);
+
+        let out = fs::read(&dst).unwrap();
+        assert_eq!(out.len(), 4096);
+        assert_eq!(&out[..1000], &data[..]);
+        assert_eq!(&out[1000..], &existing[1000..]);
+    }
+}